@@ -1,15 +1,22 @@
-use crate::pdf_converter::{ConvertParams, OutputImageType, PdfConvertError, PdfConvertResult};
+use crate::pdf_converter::{
+    format_table, ConvertManifest, ConvertParams, OutputImageType, PdfConvertError,
+    PdfConvertResult,
+};
 use crate::zip::{write_to_zip, ZipError};
 use crate::AppState;
+use async_std::io::ReadExt;
+use bytes::Bytes;
+use multer::Multipart;
 use serde::Serialize;
 use std::io::Write;
-use tempfile::Builder;
-use tide::http::headers::{HeaderValue, HeaderValues, ACCEPT};
+use tempfile::{Builder, NamedTempFile};
+use tide::http::headers::{HeaderValue, HeaderValues, ACCEPT, CONTENT_TYPE};
 use tide::log::{error, info};
 use tide::StatusCode::{BadRequest, InternalServerError, NotAcceptable, UnprocessableEntity};
 use tide::{Body, Request, Response, StatusCode};
 
 const PDF_MAGIC: &[u8] = b"%PDF";
+const MULTIPART_READ_CHUNK_SIZE: usize = 64 * 1024;
 
 #[derive(serde::Serialize)]
 struct JsonProblemResponse {
@@ -22,11 +29,15 @@ struct JsonProblemResponse {
 }
 
 pub async fn handle(mut request: Request<AppState>) -> tide::Result {
-    if request.content_type().is_none() {
-        return Ok(create_mime_error_response());
+    let mime = match request.content_type() {
+        Some(mime) => mime,
+        None => return Ok(create_mime_error_response()),
+    };
+
+    if mime.essence() == "multipart/form-data" {
+        return handle_multipart(request).await;
     }
 
-    let mime = request.content_type().unwrap();
     if mime.essence() != "application/pdf" {
         return Ok(create_mime_error_response());
     }
@@ -48,18 +59,156 @@ pub async fn handle(mut request: Request<AppState>) -> tide::Result {
     info!("{}", params);
 
     let mut pdf_temp_file = Builder::new().prefix("hpi").suffix(".pdf").tempfile()?;
-
     pdf_temp_file.write_all(&body)?;
 
+    convert_and_respond(request.state(), pdf_temp_file, params).await
+}
+
+/// Parses a `multipart/form-data` body into the same `ConvertParams` the
+/// query-string path produces, so a plain HTML `<form>` can submit a PDF
+/// file part alongside the usual conversion options as ordinary form fields.
+async fn handle_multipart(mut request: Request<AppState>) -> tide::Result {
+    let boundary = match multipart_boundary(&request) {
+        Some(boundary) => boundary,
+        None => return Ok(create_mime_error_response()),
+    };
+
+    let mut body = request.take_body();
+    let chunks = async_std::stream::from_fn(move || {
+        let body = &mut body;
+
+        async move {
+            let mut buf = vec![0u8; MULTIPART_READ_CHUNK_SIZE];
+
+            match body.read(&mut buf).await {
+                Ok(0) => None,
+                Ok(n) => {
+                    buf.truncate(n);
+
+                    Some(Ok::<_, std::io::Error>(Bytes::from(buf)))
+                }
+                Err(e) => Some(Err(e)),
+            }
+        }
+    });
+    let mut multipart = Multipart::new(chunks, boundary);
+
+    let mut params = ConvertParams::default();
+    read_accept_header(&request, &mut params);
+
+    let mut pdf_temp_file: Option<NamedTempFile> = None;
+
+    loop {
+        let mut field = match multipart.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(_) => return Ok(create_query_params_error_response()),
+        };
+
+        let Some(name) = field.name().map(str::to_string) else {
+            continue;
+        };
+
+        match name.as_str() {
+            "file" => {
+                let mut temp_file = Builder::new().prefix("hpi").suffix(".pdf").tempfile()?;
+                let mut wrote_any_bytes = false;
+
+                loop {
+                    let chunk = match field.chunk().await {
+                        Ok(Some(chunk)) => chunk,
+                        Ok(None) => break,
+                        Err(_) => return Ok(create_query_params_error_response()),
+                    };
+
+                    if !wrote_any_bytes && !chunk.starts_with(PDF_MAGIC) {
+                        return Ok(create_magic_error_response());
+                    }
+
+                    temp_file.write_all(&chunk)?;
+                    wrote_any_bytes = true;
+                }
+
+                if !wrote_any_bytes {
+                    return Ok(create_magic_error_response());
+                }
+
+                pdf_temp_file = Some(temp_file);
+            }
+            "dpi" => {
+                if let Ok(dpi) = field.text().await.unwrap_or_default().parse() {
+                    params.dpi = dpi;
+                }
+            }
+            "background_color" => {
+                params.background_color = field.text().await.unwrap_or(params.background_color);
+            }
+            "preserve_alpha" => {
+                let text = field.text().await.unwrap_or_default();
+                params.preserve_alpha = text == "true" || text == "1";
+            }
+            "output_type" => {
+                params.output_type = match field.text().await.unwrap_or_default().as_str() {
+                    "gif" => OutputImageType::Gif,
+                    "jpeg" | "jpg" => OutputImageType::Jpeg,
+                    "webp" => OutputImageType::Webp,
+                    "tiff" => OutputImageType::Tiff,
+                    "bmp" => OutputImageType::Bmp,
+                    #[cfg(feature = "avif")]
+                    "avif" => OutputImageType::Avif,
+                    _ => OutputImageType::Png,
+                };
+            }
+            "pages" => {
+                params.pages = field.text().await.ok();
+            }
+            "animate" => {
+                let text = field.text().await.unwrap_or_default();
+                params.animate = text == "true" || text == "1";
+            }
+            "frame_delay_ms" => {
+                if let Ok(frame_delay_ms) = field.text().await.unwrap_or_default().parse() {
+                    params.frame_delay_ms = frame_delay_ms;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let pdf_temp_file = match pdf_temp_file {
+        Some(pdf_temp_file) => pdf_temp_file,
+        None => return Ok(create_magic_error_response()),
+    };
+
+    info!("{}", params);
+
+    convert_and_respond(request.state(), pdf_temp_file, params).await
+}
+
+fn multipart_boundary(request: &Request<AppState>) -> Option<String> {
+    request
+        .header(CONTENT_TYPE)?
+        .as_str()
+        .split(';')
+        .find_map(|part| part.trim().strip_prefix("boundary="))
+        .map(|boundary| boundary.trim_matches('"').to_string())
+}
+
+async fn convert_and_respond(
+    state: &AppState,
+    pdf_temp_file: NamedTempFile,
+    params: ConvertParams,
+) -> tide::Result {
     let pdf_temp_file_path = pdf_temp_file.path();
     info!(
         "Wrote request body to {}",
         pdf_temp_file_path.to_str().unwrap_or("unknown path")
     );
 
-    let state = request.state();
-
-    let convert_result = state.pdf_converter.convert(pdf_temp_file_path, params);
+    let convert_result = state
+        .pdf_converter
+        .convert(pdf_temp_file_path.to_path_buf(), params)
+        .await;
 
     state.increase_conversion_counter();
 
@@ -79,15 +228,11 @@ fn read_accept_header(request: &Request<AppState>, params: &mut ConvertParams) {
 
     params.allow_zip = accept.contains("application/zip");
 
-    if accept.contains("image/gif") {
-        params.output_type = OutputImageType::Gif;
-    } else if accept.contains("image/jpeg") {
-        params.output_type = OutputImageType::Jpeg;
-    } else if accept.contains("image/webp") {
-        params.output_type = OutputImageType::Webp;
-    } else {
-        params.output_type = OutputImageType::Png;
-    }
+    params.output_type = format_table()
+        .into_iter()
+        .find(|entry| accept.contains(entry.mime))
+        .map(|entry| entry.output_type)
+        .unwrap_or(OutputImageType::Png);
 }
 
 async fn create_success_response(convert_result: PdfConvertResult) -> tide::Result {
@@ -95,7 +240,18 @@ async fn create_success_response(convert_result: PdfConvertResult) -> tide::Resu
         PdfConvertResult::Multi(multi_pages) => {
             let zip_temp_file = Builder::new().prefix("hpi").suffix(".zip").tempfile()?;
             let zip_temp_path = zip_temp_file.path();
-            let zip_result = write_to_zip(zip_temp_path, multi_pages.to_iter());
+
+            let manifest_json = if multi_pages.has_failures() {
+                Some(serde_json::to_vec(&multi_pages.manifest())?)
+            } else {
+                None
+            };
+
+            let zip_result = write_to_zip(
+                zip_temp_path,
+                multi_pages.to_iter(),
+                manifest_json.as_deref(),
+            );
 
             if let Err(zip_err) = zip_result {
                 return Ok(create_zip_error_response(zip_err));
@@ -111,6 +267,7 @@ async fn create_success_response(convert_result: PdfConvertResult) -> tide::Resu
             body.into()
         }
         PdfConvertResult::Empty => create_empty_pdf_error_response(),
+        PdfConvertResult::PartialFailure(manifest) => create_partial_failure_response(manifest),
     })
 }
 
@@ -155,48 +312,58 @@ fn create_magic_error_response() -> Response {
 }
 
 fn create_convert_error_response(e: PdfConvertError) -> Response {
-    let detail = match e {
-        PdfConvertError::LibraryLoad(ref pdfium_error) => {
-            error!("PDFium library load error: {pdfium_error}");
-
-            format!("Failed loading the PDFium library: {pdfium_error}")
-        }
-        PdfConvertError::DocumentLoad(ref pdfium_error) => {
-            error!("PDFium document load error: {pdfium_error}");
-
-            format!("Failed loading the document binary: {pdfium_error}")
-        }
-        PdfConvertError::PageRender(ref pdfium_error) => {
-            error!("PDFium page render error: {pdfium_error}");
-
-            format!("Failed rendering the PDF page: {pdfium_error}")
-        }
-        PdfConvertError::ImageWrite(ref image_error) => {
-            error!("Image write error: {image_error}");
-
-            format!("Failed writing the PDF page as image: {image_error}")
-        }
-        PdfConvertError::ImageRead(ref image_error) => {
-            error!("Image read error: {image_error}");
-
-            format!("Failed read image: {image_error}")
-        }
-        PdfConvertError::TempFile(ref io_error) => {
-            error!("Error when creating the temporary image file: {io_error}");
-
-            String::from("Unknown file write error")
-        }
+    let detail = e.detail();
+    error!("PDF convert error: {detail}");
+
+    let status = match e {
+        PdfConvertError::InvalidPageSpec(_) => BadRequest,
+        PdfConvertError::PagesOutOfRange(_) => UnprocessableEntity,
+        PdfConvertError::AnimationUnsupported(_) => BadRequest,
+        PdfConvertError::PreserveAlphaUnsupported(_) => BadRequest,
+        _ => InternalServerError,
     };
 
     let problem = JsonProblemResponse {
         _type: String::from("about:blank"),
         title: String::from("PDF convert error"),
-        status: 500,
+        status: status.into(),
         detail: Some(detail),
         instance: None,
     };
 
-    create_response_with_json(InternalServerError, &problem)
+    create_response_with_json(status, &problem)
+}
+
+/// Built when some pages rendered and some didn't and the request asked for
+/// a single combined image, which has no room to carry the manifest of
+/// failures alongside it.
+fn create_partial_failure_response(manifest: ConvertManifest) -> Response {
+    let failed_pages = manifest
+        .failed_pages
+        .iter()
+        .map(|failure| failure.page_index.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    error!(
+        "{} of {} requested pages failed to render: {failed_pages}",
+        manifest.failed_pages.len(),
+        manifest.rendered_pages.len() + manifest.failed_pages.len()
+    );
+
+    let problem = JsonProblemResponse {
+        _type: String::from("about:blank"),
+        title: String::from("Partial PDF convert failure"),
+        status: UnprocessableEntity.into(),
+        detail: Some(String::from(
+            "Some pages failed to render, so no single combined image can be returned; \
+             request a ZIP output (Accept: application/zip) to get the successful pages \
+             plus a manifest.json of the failures",
+        )),
+        instance: Some(failed_pages),
+    };
+
+    create_response_with_json(UnprocessableEntity, &problem)
 }
 
 fn create_zip_error_response(e: ZipError) -> Response {
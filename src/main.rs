@@ -1,4 +1,4 @@
-use crate::pdf_converter::PdfConverter;
+use crate::pdf_converter::{format_table, PdfConverter};
 use serde_json::json;
 use std::env;
 use std::future::Future;
@@ -16,17 +16,17 @@ mod zip;
 #[derive(Clone)]
 struct AppState {
     auth_token: String,
-    pdf_converter: PdfConverter,
+    pdf_converter: Arc<PdfConverter>,
     count_conversions: Arc<AtomicUsize>,
 }
 
 impl AppState {
-    fn new(auth_token: String, pdfium_lib: String) -> Self {
-        Self {
+    fn new(auth_token: String, pdfium_lib: String) -> tide::Result<Self> {
+        Ok(Self {
             auth_token,
-            pdf_converter: PdfConverter::new(pdfium_lib),
+            pdf_converter: Arc::new(PdfConverter::new(pdfium_lib)?),
             count_conversions: Arc::new(AtomicUsize::new(0)),
-        }
+        })
     }
 
     pub fn increase_conversion_counter(&self) {
@@ -64,7 +64,7 @@ async fn main() -> tide::Result<()> {
     let auth_token = env::var("HPI_AUTH_TOKEN").unwrap_or_else(|_e| "".to_string());
     let pdfium_lib = env::var("HPI_PDFIUM_LIB").unwrap_or_else(|_e| "".to_string());
 
-    let mut app = tide::with_state(AppState::new(auth_token.clone(), pdfium_lib));
+    let mut app = tide::with_state(AppState::new(auth_token.clone(), pdfium_lib)?);
 
     app.with(LogMiddleware::new());
 
@@ -74,6 +74,7 @@ async fn main() -> tide::Result<()> {
 
     app.at("/").get(get);
     app.at("/").post(http_post_controller::handle);
+    app.at("/formats").get(get_formats);
 
     app.listen(vec![format!("0.0.0.0:{server_port}")]).await?;
 
@@ -90,3 +91,15 @@ async fn get(request: Request<AppState>) -> tide::Result {
 
     Ok(response)
 }
+
+async fn get_formats(_request: Request<AppState>) -> tide::Result {
+    let formats: Vec<_> = format_table()
+        .into_iter()
+        .map(|entry| json!({ "mime": entry.mime, "extension": entry.extension }))
+        .collect();
+
+    let mut response = Response::new(StatusCode::Ok);
+    response.set_body(Body::from_json(&formats)?);
+
+    Ok(response)
+}
@@ -1,42 +1,123 @@
-use image::{imageops, ImageBuffer, ImageFormat, RgbImage};
-use pdfium_render::prelude::{PdfRenderConfig, Pdfium, PdfiumError, Pixels};
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{imageops, Delay, Frame, ImageBuffer, ImageFormat, Rgb, Rgba, RgbImage, RgbaImage};
+use pdfium_render::prelude::{PdfPage, PdfRenderConfig, Pdfium, PdfiumError, Pixels};
+use std::collections::BTreeSet;
 use std::fmt::{Display, Formatter};
 use std::io;
-use std::path::Path;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
 use tempfile::{Builder, NamedTempFile};
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, Clone, Copy, PartialEq, Eq)]
 pub enum OutputImageType {
     Png,
     Gif,
     Jpeg,
     Webp,
+    Tiff,
+    Bmp,
+    #[cfg(feature = "avif")]
+    Avif,
 }
 
 impl Display for OutputImageType {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let str = match self {
-            OutputImageType::Png => ".png",
-            OutputImageType::Gif => ".gif",
-            OutputImageType::Jpeg => ".jpg",
-            OutputImageType::Webp => ".webp",
-        }
-        .to_string();
-        write!(f, "{}", str)
+        write!(f, "{}", format_entry_for(*self).extension)
     }
 }
 
 impl From<&OutputImageType> for ImageFormat {
     fn from(value: &OutputImageType) -> Self {
-        match value {
-            OutputImageType::Png => ImageFormat::Png,
-            OutputImageType::Gif => ImageFormat::Gif,
-            OutputImageType::Jpeg => ImageFormat::Jpeg,
-            OutputImageType::Webp => ImageFormat::WebP,
-        }
+        format_entry_for(*value).image_format
     }
 }
 
+/// One entry of the server's supported output formats, tying an
+/// [`OutputImageType`] to the `image` crate format it maps to, the MIME
+/// essence clients negotiate with via `Accept`, the file extension used for
+/// rendered pages, and whether the format can carry an alpha channel.
+pub struct FormatEntry {
+    pub output_type: OutputImageType,
+    pub image_format: ImageFormat,
+    pub mime: &'static str,
+    pub extension: &'static str,
+    pub supports_alpha: bool,
+}
+
+/// The table of output formats this server can actually emit. AVIF is only
+/// included when built with the `avif` feature, since it pulls in an
+/// optional codec.
+pub fn format_table() -> Vec<FormatEntry> {
+    let mut formats = vec![
+        FormatEntry {
+            output_type: OutputImageType::Png,
+            image_format: ImageFormat::Png,
+            mime: "image/png",
+            extension: ".png",
+            supports_alpha: true,
+        },
+        FormatEntry {
+            output_type: OutputImageType::Gif,
+            image_format: ImageFormat::Gif,
+            mime: "image/gif",
+            extension: ".gif",
+            supports_alpha: true,
+        },
+        FormatEntry {
+            output_type: OutputImageType::Jpeg,
+            image_format: ImageFormat::Jpeg,
+            mime: "image/jpeg",
+            extension: ".jpg",
+            supports_alpha: false,
+        },
+        FormatEntry {
+            output_type: OutputImageType::Webp,
+            image_format: ImageFormat::WebP,
+            mime: "image/webp",
+            extension: ".webp",
+            supports_alpha: true,
+        },
+        FormatEntry {
+            output_type: OutputImageType::Tiff,
+            image_format: ImageFormat::Tiff,
+            mime: "image/tiff",
+            extension: ".tiff",
+            supports_alpha: true,
+        },
+        FormatEntry {
+            output_type: OutputImageType::Bmp,
+            image_format: ImageFormat::Bmp,
+            mime: "image/bmp",
+            extension: ".bmp",
+            supports_alpha: false,
+        },
+    ];
+
+    #[cfg(feature = "avif")]
+    formats.push(FormatEntry {
+        output_type: OutputImageType::Avif,
+        image_format: ImageFormat::Avif,
+        mime: "image/avif",
+        extension: ".avif",
+        supports_alpha: true,
+    });
+
+    formats
+}
+
+fn format_entry_for(output_type: OutputImageType) -> FormatEntry {
+    format_table()
+        .into_iter()
+        .find(|entry| entry.output_type == output_type)
+        .expect("every OutputImageType variant has a format table entry")
+}
+
 #[derive(serde::Deserialize)]
 #[serde(default)]
 pub struct ConvertParams {
@@ -47,6 +128,9 @@ pub struct ConvertParams {
     pub dpi: u32,
     pub preserve_alpha: bool,
     pub background_color: String,
+    pub pages: Option<String>,
+    pub animate: bool,
+    pub frame_delay_ms: u32,
 }
 
 impl Default for ConvertParams {
@@ -57,6 +141,9 @@ impl Default for ConvertParams {
             dpi: 72,
             preserve_alpha: false,
             background_color: "white".to_string(),
+            pages: None,
+            animate: false,
+            frame_delay_ms: 500,
         }
     }
 }
@@ -64,7 +151,7 @@ impl Default for ConvertParams {
 impl Display for ConvertParams {
     fn fmt(&self, fmt: &mut Formatter<'_>) -> std::fmt::Result {
         fmt.write_fmt(format_args!(
-            "Type: {}, {}, DPI {}, {}, background: {}",
+            "Type: {}, {}, DPI {}, {}, background: {}, pages: {}, animate: {}",
             self.output_type,
             match self.allow_zip {
                 true => "Multi page to ZIP",
@@ -75,45 +162,155 @@ impl Display for ConvertParams {
                 true => "Preserve alpha",
                 false => "Remove alpha",
             },
-            self.background_color
+            self.background_color,
+            self.pages.as_deref().unwrap_or("all"),
+            match self.animate {
+                true => format!("{}ms/frame", self.frame_delay_ms),
+                false => String::from("no"),
+            }
         ))
     }
 }
 
 pub enum PdfConvertError {
-    LibraryLoad(PdfiumError),
     DocumentLoad(PdfiumError),
     PageRender(PdfiumError),
     ImageWrite(image::ImageError),
     ImageRead(image::ImageError),
     TempFile(io::Error),
+    Worker,
+    RenderPanic,
+    InvalidPageSpec(String),
+    PagesOutOfRange(Vec<usize>),
+    AnimationUnsupported(OutputImageType),
+    PreserveAlphaUnsupported(OutputImageType),
+}
+
+impl PdfConvertError {
+    /// A human-readable detail message, shared between the top-level error
+    /// response and the per-page failure entries in a [`ConvertManifest`].
+    pub fn detail(&self) -> String {
+        match self {
+            PdfConvertError::DocumentLoad(e) => format!("Failed loading the document binary: {e}"),
+            PdfConvertError::PageRender(e) => format!("Failed rendering the PDF page: {e}"),
+            PdfConvertError::ImageWrite(e) => format!("Failed writing the PDF page as image: {e}"),
+            PdfConvertError::ImageRead(e) => format!("Failed read image: {e}"),
+            PdfConvertError::TempFile(_) => String::from("Unknown file write error"),
+            PdfConvertError::Worker => String::from("The rendering worker is unavailable"),
+            PdfConvertError::RenderPanic => {
+                String::from("The rendering worker panicked while processing this document")
+            }
+            PdfConvertError::InvalidPageSpec(spec) => {
+                format!("The \"pages\" parameter could not be parsed: \"{spec}\"")
+            }
+            PdfConvertError::PagesOutOfRange(pages) => {
+                let pages = pages
+                    .iter()
+                    .map(usize::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                format!("The following requested pages do not exist: {pages}")
+            }
+            PdfConvertError::AnimationUnsupported(format) => {
+                format!(
+                    "Animated output is not supported for {} in this build",
+                    format_entry_for(*format).mime
+                )
+            }
+            PdfConvertError::PreserveAlphaUnsupported(format) => {
+                format!(
+                    "preserve_alpha=true is not supported for {}, which has no alpha channel",
+                    format_entry_for(*format).mime
+                )
+            }
+        }
+    }
+}
+
+/// A single page that failed to render or encode, keeping the 1-based page
+/// number alongside the error that caused it.
+struct PageFailure {
+    page_index: usize,
+    error: PdfConvertError,
+}
+
+/// Describes which pages made it into a result and which didn't, so a client
+/// can tell a partial document from a complete one instead of just losing
+/// the failed pages silently.
+#[derive(serde::Serialize)]
+pub struct ConvertManifest {
+    pub rendered_pages: Vec<usize>,
+    pub failed_pages: Vec<PageFailureDetail>,
+}
+
+/// `page_index` is the 1-based page number, matching how [`PagesOutOfRange`]
+/// and a user's own `pages` spec refer to pages.
+///
+/// [`PagesOutOfRange`]: PdfConvertError::PagesOutOfRange
+#[derive(serde::Serialize)]
+pub struct PageFailureDetail {
+    pub page_index: usize,
+    pub detail: String,
+}
+
+struct RenderedPage {
+    page_index: usize,
+    temp_file: NamedTempFile,
 }
 
 pub struct MultiPagesResult {
-    temp_files: Vec<NamedTempFile>,
+    pages: Vec<RenderedPage>,
+    failures: Vec<PageFailure>,
 }
 
 impl MultiPagesResult {
     fn new() -> Self {
         Self {
-            temp_files: Vec::new(),
+            pages: Vec::new(),
+            failures: Vec::new(),
         }
     }
 
-    fn push(&mut self, path: NamedTempFile) {
-        self.temp_files.push(path);
+    fn push(&mut self, page_index: usize, temp_file: NamedTempFile) {
+        self.pages.push(RenderedPage {
+            page_index,
+            temp_file,
+        });
+    }
+
+    fn push_failure(&mut self, page_index: usize, error: PdfConvertError) {
+        self.failures.push(PageFailure { page_index, error });
     }
 
     fn is_empty(&self) -> bool {
-        self.temp_files.is_empty()
+        self.pages.is_empty()
     }
 
     fn is_single(&self) -> bool {
-        self.temp_files.len() == 1
+        self.pages.len() == 1
+    }
+
+    pub fn has_failures(&self) -> bool {
+        !self.failures.is_empty()
     }
 
-    pub fn to_iter(&self) -> std::slice::Iter<'_, NamedTempFile> {
-        self.temp_files.iter()
+    pub fn to_iter(&self) -> impl Iterator<Item = &NamedTempFile> {
+        self.pages.iter().map(|page| &page.temp_file)
+    }
+
+    pub fn manifest(&self) -> ConvertManifest {
+        ConvertManifest {
+            rendered_pages: self.pages.iter().map(|page| page.page_index).collect(),
+            failed_pages: self
+                .failures
+                .iter()
+                .map(|failure| PageFailureDetail {
+                    page_index: failure.page_index,
+                    detail: failure.error.detail(),
+                })
+                .collect(),
+        }
     }
 }
 
@@ -121,80 +318,426 @@ pub enum PdfConvertResult {
     Empty,
     Single(NamedTempFile),
     Multi(MultiPagesResult),
+    /// All requested pages were attempted but at least one failed and the
+    /// request asked for a single combined image, which can't carry a
+    /// manifest alongside it. Request a ZIP output instead to get the
+    /// successful pages plus a `manifest.json` of the failures.
+    PartialFailure(ConvertManifest),
+}
+
+/// A unit of render work handed off to the [`render_worker`] thread, together
+/// with the channel its result should be sent back on.
+struct RenderJob {
+    pdf_file_path: PathBuf,
+    params: ConvertParams,
+    reply: mpsc::Sender<Result<PdfConvertResult, PdfConvertError>>,
 }
 
-#[derive(Clone)]
 pub struct PdfConverter {
-    pdfium_lib: String,
+    workers: Vec<Mutex<mpsc::Sender<RenderJob>>>,
+    next_worker: AtomicUsize,
 }
 
 impl PdfConverter {
-    pub fn new(pdfium_lib: String) -> Self {
-        Self { pdfium_lib }
+    /// Binds the PDFium library once per worker and hands each binding to its
+    /// own dedicated thread, since `Pdfium`/`PdfDocument` are not `Send` and
+    /// can't be shared across the async executor's threads or with each
+    /// other. The pool is sized to the available parallelism so rendering
+    /// isn't serialized onto a single core; requests are dispatched to
+    /// workers round-robin by sending a [`RenderJob`] and awaiting its reply.
+    pub fn new(pdfium_lib: String) -> Result<Self, PdfiumError> {
+        let worker_count = thread::available_parallelism()
+            .map(|count| count.get())
+            .unwrap_or(1);
+
+        let mut workers = Vec::with_capacity(worker_count);
+
+        for worker_index in 0..worker_count {
+            let bindings = Pdfium::bind_to_library(Pdfium::pdfium_platform_library_name_at_path(
+                &pdfium_lib,
+            ))
+            .or_else(|_| Pdfium::bind_to_system_library())?;
+
+            let (jobs_tx, jobs_rx) = mpsc::channel::<RenderJob>();
+
+            thread::Builder::new()
+                .name(format!("pdfium-render-worker-{worker_index}"))
+                .spawn(move || render_worker(Pdfium::new(bindings), jobs_rx))
+                .expect("failed to spawn the PDFium render worker thread");
+
+            workers.push(Mutex::new(jobs_tx));
+        }
+
+        Ok(Self {
+            workers,
+            next_worker: AtomicUsize::new(0),
+        })
     }
 
-    pub fn convert(
+    pub async fn convert(
         &self,
-        pdf_file_path: &Path,
+        pdf_file_path: PathBuf,
         params: ConvertParams,
     ) -> Result<PdfConvertResult, PdfConvertError> {
-        let pdfium = Pdfium::new(
-            Pdfium::bind_to_library(Pdfium::pdfium_platform_library_name_at_path(
-                &self.pdfium_lib,
-            ))
-            .or_else(|_| Pdfium::bind_to_system_library())
-            .map_err(PdfConvertError::LibraryLoad)?,
-        );
-
-        let document = pdfium
-            .load_pdf_from_file(pdf_file_path, None)
-            .map_err(PdfConvertError::DocumentLoad)?;
-
-        let mut result = MultiPagesResult::new();
-        let dpi = params.dpi as f32;
-        let image_format = ImageFormat::from(&params.output_type);
-
-        for (index, page) in document.pages().iter().enumerate() {
-            let image_temp_file = Builder::new()
-                .prefix(&format!("{index}-hpi"))
-                .suffix(&params.output_type.to_string())
-                .tempfile()
-                .map_err(PdfConvertError::TempFile)?;
-            let image_temp_path = image_temp_file.path();
-
-            let width_inches = page.width().to_inches();
-            let width_px = width_inches * dpi;
-            let render_config = PdfRenderConfig::new().set_target_width(width_px.round() as Pixels);
-
-            page.render_with_config(&render_config)
-                .map_err(PdfConvertError::PageRender)?
-                .as_image()
-                .into_rgb8()
-                .save_with_format(image_temp_path, image_format)
-                .map_err(PdfConvertError::ImageWrite)?;
-
-            result.push(image_temp_file);
-        }
+        let (reply_tx, reply_rx) = mpsc::channel();
+
+        let worker_index = self.next_worker.fetch_add(1, Ordering::Relaxed) % self.workers.len();
+
+        self.workers[worker_index]
+            .lock()
+            .map_err(|_| PdfConvertError::Worker)?
+            .send(RenderJob {
+                pdf_file_path,
+                params,
+                reply: reply_tx,
+            })
+            .map_err(|_| PdfConvertError::Worker)?;
+
+        async_std::task::spawn_blocking(move || match reply_rx.recv() {
+            Ok(result) => result,
+            Err(_) => Err(PdfConvertError::Worker),
+        })
+        .await
+    }
+}
+
+/// Owns the bound `Pdfium` instance and renders jobs one at a time on its own
+/// thread, keeping the CPU-bound work off the async executor. A single job is
+/// run inside `catch_unwind` so a document that panics deep in `pdfium_render`
+/// or `image` fails only that request instead of taking the worker thread
+/// (and every request queued behind it) down with it.
+fn render_worker(pdfium: Pdfium, jobs: mpsc::Receiver<RenderJob>) {
+    for job in jobs {
+        let RenderJob {
+            pdf_file_path,
+            params,
+            reply,
+        } = job;
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            render_document(&pdfium, &pdf_file_path, params)
+        }))
+        .unwrap_or(Err(PdfConvertError::RenderPanic));
+
+        let _ = reply.send(result);
+    }
+}
+
+fn render_document(
+    pdfium: &Pdfium,
+    pdf_file_path: &Path,
+    params: ConvertParams,
+) -> Result<PdfConvertResult, PdfConvertError> {
+    if params.preserve_alpha && !format_entry_for(params.output_type).supports_alpha {
+        return Err(PdfConvertError::PreserveAlphaUnsupported(
+            params.output_type,
+        ));
+    }
+
+    let document = pdfium
+        .load_pdf_from_file(pdf_file_path, None)
+        .map_err(PdfConvertError::DocumentLoad)?;
+
+    let selected_pages = params.pages.as_deref().map(parse_page_spec).transpose()?;
 
-        if result.is_empty() {
-            return Ok(PdfConvertResult::Empty);
+    if let Some(ref pages) = selected_pages {
+        let page_count = document.pages().len() as usize;
+        let out_of_range: Vec<usize> = pages
+            .iter()
+            .filter(|&&index| index >= page_count)
+            .map(|&index| index + 1)
+            .collect();
+
+        if !out_of_range.is_empty() {
+            return Err(PdfConvertError::PagesOutOfRange(out_of_range));
         }
+    }
 
-        if !params.allow_zip {
-            if result.is_single() {
-                let first = result.temp_files.remove(0);
+    let mut result = MultiPagesResult::new();
+    let dpi = params.dpi as f32;
+    let image_format = ImageFormat::from(&params.output_type);
 
-                return Ok(PdfConvertResult::Single(first));
+    for (index, page) in document.pages().iter().enumerate() {
+        if let Some(ref pages) = selected_pages {
+            if !pages.contains(&index) {
+                continue;
             }
+        }
+
+        match render_page(
+            &page,
+            index,
+            dpi,
+            image_format,
+            &params.output_type,
+            params.preserve_alpha,
+            &params.background_color,
+        ) {
+            Ok(image_temp_file) => result.push(index + 1, image_temp_file),
+            Err(error) => result.push_failure(index + 1, error),
+        }
+    }
+
+    if result.is_empty() {
+        return match result.failures.into_iter().next() {
+            Some(failure) => Err(failure.error),
+            None => Ok(PdfConvertResult::Empty),
+        };
+    }
+
+    if !params.allow_zip {
+        if result.has_failures() {
+            return Ok(PdfConvertResult::PartialFailure(result.manifest()));
+        }
+
+        if result.is_single() {
+            let first = result.pages.remove(0).temp_file;
 
-            return Ok(PdfConvertResult::Single(combine_images(
+            return Ok(PdfConvertResult::Single(first));
+        }
+
+        if params.animate {
+            return Ok(PdfConvertResult::Single(encode_animation(
                 result,
-                image_format,
+                params.output_type,
+                params.frame_delay_ms,
             )?));
         }
 
-        Ok(PdfConvertResult::Multi(result))
+        return Ok(PdfConvertResult::Single(combine_images(
+            result,
+            image_format,
+        )?));
+    }
+
+    Ok(PdfConvertResult::Multi(result))
+}
+
+fn render_page(
+    page: &PdfPage<'_>,
+    index: usize,
+    dpi: f32,
+    image_format: ImageFormat,
+    output_type: &OutputImageType,
+    preserve_alpha: bool,
+    background_color: &str,
+) -> Result<NamedTempFile, PdfConvertError> {
+    let image_temp_file = Builder::new()
+        .prefix(&format!("{index}-hpi"))
+        .suffix(&output_type.to_string())
+        .tempfile()
+        .map_err(PdfConvertError::TempFile)?;
+    let image_temp_path = image_temp_file.path();
+
+    let width_inches = page.width().to_inches();
+    let width_px = width_inches * dpi;
+    let render_config = PdfRenderConfig::new().set_target_width(width_px.round() as Pixels);
+
+    let rendered = page
+        .render_with_config(&render_config)
+        .map_err(PdfConvertError::PageRender)?
+        .as_image()
+        .into_rgba8();
+
+    if preserve_alpha {
+        rendered
+            .save_with_format(image_temp_path, image_format)
+            .map_err(PdfConvertError::ImageWrite)?;
+    } else {
+        let background = parse_background_color(background_color);
+
+        flatten_onto_background(&rendered, background)
+            .save_with_format(image_temp_path, image_format)
+            .map_err(PdfConvertError::ImageWrite)?;
+    }
+
+    Ok(image_temp_file)
+}
+
+/// Parses a background color spec (`"white"`, `"black"`, `"transparent"`, or
+/// a `#rrggbb`/`#rrggbbaa` hex string, with the `#` optional) used to flatten
+/// a page when `preserve_alpha` is off. Alpha is opaque unless a valid fourth
+/// hex pair follows the first three. Falls back to opaque white when the spec
+/// does not start with three valid hex pairs.
+fn parse_background_color(spec: &str) -> Rgba<u8> {
+    match spec.trim().to_ascii_lowercase().as_str() {
+        "white" => return Rgba([255, 255, 255, 255]),
+        "black" => return Rgba([0, 0, 0, 255]),
+        "transparent" => return Rgba([0, 0, 0, 0]),
+        _ => {}
+    }
+
+    let hex = spec.trim().trim_start_matches('#');
+    let channel = |i: usize| -> Option<u8> { u8::from_str_radix(hex.get(i..i + 2)?, 16).ok() };
+
+    match (channel(0), channel(2), channel(4)) {
+        (Some(r), Some(g), Some(b)) => Rgba([r, g, b, channel(6).unwrap_or(255)]),
+        _ => Rgba([255, 255, 255, 255]),
+    }
+}
+
+/// Alpha-composites a rendered page onto a solid background. `imageops::overlay`
+/// only blits pixels of matching types rather than blending them, so formats
+/// without alpha support need this instead when `preserve_alpha` is off.
+fn flatten_onto_background(image: &RgbaImage, background: Rgba<u8>) -> RgbImage {
+    let mut output = RgbImage::new(image.width(), image.height());
+
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let alpha = pixel[3] as f32 / 255.0;
+        let blend = |channel: usize| -> u8 {
+            (pixel[channel] as f32 * alpha + background[channel] as f32 * (1.0 - alpha)).round()
+                as u8
+        };
+
+        output.put_pixel(x, y, Rgb([blend(0), blend(1), blend(2)]));
+    }
+
+    output
+}
+
+/// Parses a 1-based page spec such as `"1,3,5-8"` into an ordered set of
+/// zero-based page indices. Ranges must run low to high and pages are
+/// numbered from 1, matching how a user would refer to a page in a document.
+fn parse_page_spec(spec: &str) -> Result<BTreeSet<usize>, PdfConvertError> {
+    let invalid = || PdfConvertError::InvalidPageSpec(spec.to_string());
+    let mut indices = BTreeSet::new();
+
+    for part in spec.split(',') {
+        let part = part.trim();
+
+        if part.is_empty() {
+            continue;
+        }
+
+        let (start, end) = match part.split_once('-') {
+            Some((start, end)) => (
+                start.trim().parse::<usize>().map_err(|_| invalid())?,
+                end.trim().parse::<usize>().map_err(|_| invalid())?,
+            ),
+            None => {
+                let page = part.parse::<usize>().map_err(|_| invalid())?;
+
+                (page, page)
+            }
+        };
+
+        if start == 0 || end < start {
+            return Err(invalid());
+        }
+
+        indices.extend((start - 1)..=(end - 1));
+    }
+
+    Ok(indices)
+}
+
+/// Encodes each page as a frame of an animated image instead of stacking
+/// them into one tall still image, giving clients a single-file preview of
+/// the whole document.
+fn encode_animation(
+    result: MultiPagesResult,
+    output_type: OutputImageType,
+    frame_delay_ms: u32,
+) -> Result<NamedTempFile, PdfConvertError> {
+    match output_type {
+        OutputImageType::Gif => encode_gif_animation(result, frame_delay_ms),
+        OutputImageType::Webp => encode_webp_animation(result, frame_delay_ms),
+        other => Err(PdfConvertError::AnimationUnsupported(other)),
+    }
+}
+
+fn animation_frames(
+    result: &MultiPagesResult,
+    frame_delay_ms: u32,
+) -> Result<Vec<Frame>, PdfConvertError> {
+    let delay = Delay::from_saturating_duration(Duration::from_millis(u64::from(frame_delay_ms)));
+
+    result
+        .to_iter()
+        .map(|image_file| {
+            let image = image::open(image_file.path())
+                .map_err(PdfConvertError::ImageRead)?
+                .into_rgba8();
+
+            Ok(Frame::from_parts(image, 0, 0, delay))
+        })
+        .collect()
+}
+
+fn encode_gif_animation(
+    result: MultiPagesResult,
+    frame_delay_ms: u32,
+) -> Result<NamedTempFile, PdfConvertError> {
+    let frames = animation_frames(&result, frame_delay_ms)?;
+
+    let image_temp_file = Builder::new()
+        .prefix("hpi")
+        .suffix(".gif")
+        .tempfile()
+        .map_err(PdfConvertError::TempFile)?;
+
+    let file = image_temp_file.reopen().map_err(PdfConvertError::TempFile)?;
+    let mut encoder = GifEncoder::new(file);
+
+    encoder
+        .set_repeat(Repeat::Infinite)
+        .map_err(PdfConvertError::ImageWrite)?;
+    encoder
+        .encode_frames(frames)
+        .map_err(PdfConvertError::ImageWrite)?;
+
+    Ok(image_temp_file)
+}
+
+#[cfg(feature = "webp-animation")]
+fn encode_webp_animation(
+    result: MultiPagesResult,
+    frame_delay_ms: u32,
+) -> Result<NamedTempFile, PdfConvertError> {
+    use webp_animation::{Encoder as WebpAnimEncoder, EncoderOptions};
+
+    let frames = animation_frames(&result, frame_delay_ms)?;
+    let (width, height) = frames
+        .first()
+        .map(|frame| frame.buffer().dimensions())
+        .ok_or(PdfConvertError::AnimationUnsupported(OutputImageType::Webp))?;
+
+    let mut encoder = WebpAnimEncoder::new_with_options(width, height, EncoderOptions::default())
+        .map_err(|_| PdfConvertError::AnimationUnsupported(OutputImageType::Webp))?;
+
+    let mut timestamp_ms = 0i32;
+
+    for frame in &frames {
+        encoder
+            .add_frame(frame.buffer(), timestamp_ms)
+            .map_err(|_| PdfConvertError::AnimationUnsupported(OutputImageType::Webp))?;
+
+        timestamp_ms += frame_delay_ms as i32;
     }
+
+    let webp_data = encoder
+        .finalize(timestamp_ms)
+        .map_err(|_| PdfConvertError::AnimationUnsupported(OutputImageType::Webp))?;
+
+    let mut image_temp_file = Builder::new()
+        .prefix("hpi")
+        .suffix(".webp")
+        .tempfile()
+        .map_err(PdfConvertError::TempFile)?;
+
+    image_temp_file
+        .write_all(&webp_data)
+        .map_err(PdfConvertError::TempFile)?;
+
+    Ok(image_temp_file)
+}
+
+#[cfg(not(feature = "webp-animation"))]
+fn encode_webp_animation(
+    _result: MultiPagesResult,
+    _frame_delay_ms: u32,
+) -> Result<NamedTempFile, PdfConvertError> {
+    Err(PdfConvertError::AnimationUnsupported(OutputImageType::Webp))
 }
 
 fn combine_images(
@@ -208,7 +751,7 @@ fn combine_images(
 
     let image_temp_path = image_temp_file.path();
 
-    let image_files = result.temp_files.iter();
+    let image_files = result.to_iter();
     let mut images: Vec<RgbImage> = Vec::new();
     let mut width = 0i64;
     let mut height = 0i64;
@@ -240,3 +783,84 @@ fn combine_images(
 
     Ok(image_temp_file)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_page_spec_single_pages() {
+        let indices = parse_page_spec("1,3,5").unwrap();
+
+        assert_eq!(indices.into_iter().collect::<Vec<_>>(), vec![0, 2, 4]);
+    }
+
+    #[test]
+    fn parse_page_spec_range() {
+        let indices = parse_page_spec("2-4").unwrap();
+
+        assert_eq!(indices.into_iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn parse_page_spec_mixed_and_overlapping() {
+        let indices = parse_page_spec("1, 3-5, 4").unwrap();
+
+        assert_eq!(indices.into_iter().collect::<Vec<_>>(), vec![0, 2, 3, 4]);
+    }
+
+    #[test]
+    fn parse_page_spec_rejects_zero() {
+        assert!(matches!(
+            parse_page_spec("0"),
+            Err(PdfConvertError::InvalidPageSpec(_))
+        ));
+    }
+
+    #[test]
+    fn parse_page_spec_rejects_descending_range() {
+        assert!(matches!(
+            parse_page_spec("5-3"),
+            Err(PdfConvertError::InvalidPageSpec(_))
+        ));
+    }
+
+    #[test]
+    fn parse_page_spec_rejects_garbage() {
+        assert!(matches!(
+            parse_page_spec("one"),
+            Err(PdfConvertError::InvalidPageSpec(_))
+        ));
+    }
+
+    #[test]
+    fn parse_background_color_named() {
+        assert_eq!(parse_background_color("white"), Rgba([255, 255, 255, 255]));
+        assert_eq!(parse_background_color("black"), Rgba([0, 0, 0, 255]));
+        assert_eq!(parse_background_color("transparent"), Rgba([0, 0, 0, 0]));
+        assert_eq!(parse_background_color("WHITE"), Rgba([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn parse_background_color_hex_rgb() {
+        assert_eq!(parse_background_color("#ff0000"), Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn parse_background_color_hex_rgba() {
+        assert_eq!(parse_background_color("#00ff0080"), Rgba([0, 255, 0, 0x80]));
+    }
+
+    #[test]
+    fn parse_background_color_three_digit_hex_falls_back_to_white() {
+        // Shorthand 3-digit hex (e.g. "#fff") isn't expanded today, so it
+        // doesn't match any channel slice and falls back to opaque white
+        // like any other unrecognized spec.
+        assert_eq!(parse_background_color("#fff"), Rgba([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn parse_background_color_unrecognized_falls_back_to_white() {
+        assert_eq!(parse_background_color("not-a-color"), Rgba([255, 255, 255, 255]));
+    }
+}
@@ -12,9 +12,10 @@ pub enum ZipError {
     ZipLib(zip::result::ZipError),
 }
 
-pub fn write_to_zip<P: AsRef<Path>>(
+pub fn write_to_zip<'a, P: AsRef<Path>>(
     zip_path: P,
-    files: std::slice::Iter<'_, NamedTempFile>,
+    files: impl Iterator<Item = &'a NamedTempFile>,
+    manifest: Option<&[u8]>,
 ) -> Result<(), ZipError> {
     let file = File::create(zip_path).map_err(ZipError::Io)?;
     let mut archive = zip::ZipWriter::new(file);
@@ -38,6 +39,13 @@ pub fn write_to_zip<P: AsRef<Path>>(
         archive.write_all(&file_buffer).map_err(ZipError::IoWrite)?;
     }
 
+    if let Some(manifest) = manifest {
+        archive
+            .start_file("manifest.json", options)
+            .map_err(ZipError::ZipLib)?;
+        archive.write_all(manifest).map_err(ZipError::IoWrite)?;
+    }
+
     archive.finish().map_err(ZipError::ZipLib)?;
 
     Ok(())